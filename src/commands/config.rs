@@ -3,9 +3,16 @@
 pub mod builder;
 
 use self::builder::Builder;
-use crate::prelude::*;
-use abscissa_core::{Command, Options, Runnable};
-use std::path::PathBuf;
+use crate::{
+    application::{app_config, APPLICATION},
+    commands::CONFIG_FILE,
+    prelude::*,
+};
+use abscissa_core::{Application, Command, Options, Runnable};
+use libra_config::config::RoleType;
+use std::convert::TryFrom;
+use std::path::{Path, PathBuf};
+use std::process::exit;
 
 /// `config` subcommand
 #[derive(Command, Debug, Options)]
@@ -13,16 +20,76 @@ pub struct ConfigCmd {
     /// Directory where config files will be output to
     #[options(short = "o", long = "output", help = "output directory")]
     output_dir: Option<PathBuf>,
+
+    /// Number of nodes to generate (a local swarm when greater than one)
+    #[options(short = "n", long = "nodes", help = "number of nodes to generate")]
+    num_nodes: Option<usize>,
+
+    /// Node role: `validator` (default) or `full_node`
+    #[options(long = "role", help = "node role: validator or full_node")]
+    role: Option<String>,
+
+    /// `peer_info.toml`-formatted files of recommended seed peers to embed
+    /// in every generated node's seed peer list
+    #[options(
+        long = "seed-peers",
+        help = "peer_info.toml files of recommended seed peers"
+    )]
+    seed_peers: Vec<PathBuf>,
 }
 
 impl Runnable for ConfigCmd {
     fn run(&self) {
         let mut builder = Builder::new();
 
+        // Merge in `open-libra.toml`, if one was loaded; relative paths in
+        // it are resolved against the config file's own directory.
+        let app_config = app_config();
+        let config_dir = config_dir();
+
+        if let Some(output_dir) = &app_config.output_dir {
+            builder.with_output_dir(resolve(&config_dir, output_dir));
+        }
+        if let Some(listen_address) = &app_config.listen_address {
+            builder.with_listen_address(listen_address);
+        }
+        if let Some(advertised_address) = &app_config.advertised_address {
+            builder.with_advertised_address(advertised_address);
+        }
+        if let Some(role) = &app_config.role {
+            builder.with_role(self.parse_role(role));
+        }
+        if let Some(key_seed) = &app_config.key_seed {
+            builder.with_key_seed(self.parse_key_seed(key_seed));
+        }
+        if let Some(is_permissioned) = app_config.is_permissioned {
+            builder.with_is_permissioned(is_permissioned);
+        }
+        if !app_config.seed_peers.is_empty() {
+            let seed_peers = app_config
+                .seed_peers
+                .iter()
+                .map(|path| resolve(&config_dir, path));
+            builder.with_seed_peers(seed_peers);
+        }
+
+        // CLI flags take precedence over the config file.
         if let Some(output_dir) = &self.output_dir {
             builder.with_output_dir(output_dir);
         }
 
+        if let Some(num_nodes) = self.num_nodes {
+            builder.with_num_nodes(self.parse_num_nodes(num_nodes));
+        }
+
+        if let Some(role) = &self.role {
+            builder.with_role(self.parse_role(role));
+        }
+
+        if !self.seed_peers.is_empty() {
+            builder.with_seed_peers(self.seed_peers.clone());
+        }
+
         builder.build().unwrap();
 
         status_ok!("Success", "all configuration files generated successfully");
@@ -32,3 +99,61 @@ impl Runnable for ConfigCmd {
         );
     }
 }
+
+impl ConfigCmd {
+    /// Parse the `--role` option, exiting with an error on an unknown value
+    fn parse_role(&self, role: &str) -> RoleType {
+        match role {
+            "validator" => RoleType::Validator,
+            "full_node" => RoleType::FullNode,
+            _ => {
+                status_err!("invalid --role {:?}: expected validator or full_node", role);
+                exit(1);
+            }
+        }
+    }
+
+    /// Validate the `-n/--nodes` option, exiting with an error on zero
+    /// rather than panicking deeper in `Builder`.
+    fn parse_num_nodes(&self, num_nodes: usize) -> usize {
+        if num_nodes == 0 {
+            status_err!("invalid --nodes 0: must generate at least one node");
+            exit(1);
+        }
+
+        num_nodes
+    }
+
+    /// Parse the `key_seed` config value (hex-encoded), exiting with an
+    /// error if it isn't valid hex or isn't exactly 32 bytes.
+    fn parse_key_seed(&self, key_seed: &str) -> [u8; 32] {
+        let bytes = hex::decode(key_seed).unwrap_or_else(|e| {
+            status_err!("invalid key_seed in {}: {}", CONFIG_FILE, e);
+            exit(1);
+        });
+
+        <[u8; 32]>::try_from(bytes.as_slice()).unwrap_or_else(|_| {
+            status_err!("key_seed in {} must be exactly 32 bytes", CONFIG_FILE);
+            exit(1);
+        })
+    }
+}
+
+/// Directory `open-libra.toml`'s relative paths are resolved against: the
+/// loaded config file's own parent directory, or `.` if none was loaded.
+fn config_dir() -> PathBuf {
+    APPLICATION
+        .config_path()
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Resolve `path` against `dir` if it's relative; absolute paths pass through.
+fn resolve(dir: &Path, path: &Path) -> PathBuf {
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        dir.join(path)
+    }
+}
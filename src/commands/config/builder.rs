@@ -9,20 +9,30 @@ use crate::{
     prelude::*,
 };
 use libra_config::{
-    config::{ConsensusConfig, NetworkConfig, NodeConfig, PersistableConfig, RoleType},
+    config::{
+        ConsensusConfig, NetworkConfig, NodeConfig, PersistableConfig, RoleType, SeedPeersConfig,
+    },
     keys::{ConsensusKeyPair, NetworkKeyPairs},
     trusted_peers::{
         ConfigHelpers, ConsensusPeersConfig, ConsensusPrivateKey, NetworkPeersConfig,
         NetworkPrivateKeys,
     },
 };
-use parity_multiaddr::Multiaddr;
-use std::path::{Path, PathBuf};
+use libra_crypto::ed25519::{Ed25519PrivateKey, Ed25519PublicKey};
+use libra_types::account_address::AccountAddress;
+use parity_multiaddr::{Multiaddr, Protocol};
+use std::collections::HashMap;
+use std::convert::TryFrom;
 use std::fs;
+use std::path::{Path, PathBuf};
 
 /// Default address to listen on
 pub const DEFAULT_LISTEN_ADDRESS: &str = "/ip4/127.0.0.1";
 
+/// First TCP port assigned to a generated swarm's nodes; each
+/// subsequent node takes the next port.
+const SWARM_BASE_PORT: u16 = 6180;
+
 /// Libra configuration builder
 pub struct Builder {
     /// Output directory
@@ -42,6 +52,13 @@ pub struct Builder {
 
     /// Is this network permissioned?
     is_permissioned: bool,
+
+    /// Number of nodes to generate (a swarm when greater than one)
+    num_nodes: usize,
+
+    /// `peer_info.toml`-formatted files describing the recommended seed
+    /// peers to embed in every generated node's seed peer list
+    seed_peers: Vec<PathBuf>,
 }
 
 impl Default for Builder {
@@ -53,6 +70,8 @@ impl Default for Builder {
             key_seed: None,
             role: RoleType::Validator,
             is_permissioned: true, // TODO(tarcieri): set this to false
+            num_nodes: 1,
+            seed_peers: Vec::new(),
         }
     }
 }
@@ -87,47 +106,205 @@ impl Builder {
         self
     }
 
-    /// Configure whether or not the network is permissioned
+    /// Configure whether or not the network is permissioned. Permissioned
+    /// (the default) generates validator identities registered in a
+    /// trusted `NetworkPeersConfig`/`ConsensusPeersConfig`; permissionless
+    /// generates standalone network identities with no trusted-peer
+    /// gating and no `peer_info.toml`.
     pub fn with_is_permissioned(&mut self, is_permissioned: bool) -> &mut Self {
-        // TODO(tarcieri): support permissionless networks
-        assert!(
-            !is_permissioned,
-            "support for `is_permissioned: false` unimplemented"
-        );
+        self.is_permissioned = is_permissioned;
         self
     }
 
-    /// Build the configuration, writing the output to `output_dir`
-    pub fn build(self) -> Result<NodeConfig, Error> {
-        assert_eq!(
-            self.role,
-            RoleType::Validator,
-            "only validator role is presently supported"
-        );
+    /// Set the node's `RoleType`. Full nodes don't participate in
+    /// consensus, so no `ConsensusKeyPair` is generated for them.
+    pub fn with_role(&mut self, role: RoleType) -> &mut Self {
+        self.role = role;
+        self
+    }
+
+    /// Set the number of nodes to generate. Values greater than one
+    /// produce a local devnet swarm: one numbered subdirectory per
+    /// node, each with its own keys and (when permissioned) `peer_info.toml`.
+    ///
+    /// `num_nodes` must be nonzero; this panics rather than producing a
+    /// nonsensical swarm of zero nodes. Callers taking this
+    /// value from untrusted input (e.g. CLI flags) should validate it
+    /// themselves first and report a clean error instead of hitting this.
+    pub fn with_num_nodes(&mut self, num_nodes: usize) -> &mut Self {
+        assert!(num_nodes > 0, "must generate at least one node");
+        self.num_nodes = num_nodes;
+        self
+    }
+
+    /// Recommend a default set of peers (read from `peer_info.toml`-formatted
+    /// files) for every generated node to dial on startup.
+    pub fn with_seed_peers(&mut self, seed_peers: impl IntoIterator<Item = PathBuf>) -> &mut Self {
+        self.seed_peers = seed_peers.into_iter().collect();
+        self
+    }
 
+    /// Build the configuration, writing the output to `output_dir`. Returns
+    /// one `NodeConfig` per generated node.
+    pub fn build(self) -> Result<Vec<NodeConfig>, Error> {
         fs::create_dir_all(self.output_dir.as_path()).expect("Can not create output directory");
 
+        if self.role == RoleType::Validator && self.is_permissioned {
+            self.build_permissioned_validators()
+        } else {
+            self.build_standalone_nodes()
+        }
+    }
+
+    /// Build a swarm of permissioned validators that share one trusted
+    /// `ConsensusPeersConfig`/`NetworkPeersConfig`, as generated by
+    /// `ConfigHelpers::gen_validator_nodes`.
+    fn build_permissioned_validators(self) -> Result<Vec<NodeConfig>, Error> {
         // Use the OS RNG to generate a seed unless one has been explicitly provided
-        let key_seed = self.key_seed.unwrap_or_else(|| {
-            let mut s = [0u8; 32];
-            getrandom::getrandom(&mut s).expect("RNG failure!");
-            s
-        });
+        let key_seed = self.key_seed.unwrap_or_else(random_seed);
 
         // Generate private keys as well as consensus and network configs
+        // shared by every validator in the swarm.
         let (private_keys, consensus_peers_config, network_peers_config) =
-            ConfigHelpers::gen_validator_nodes(1, Some(key_seed));
+            ConfigHelpers::gen_validator_nodes(self.num_nodes, Some(key_seed));
+
+        // Sort by peer ID so subdirectory numbering is stable across runs.
+        let mut private_keys: Vec<_> = private_keys.into_iter().collect();
+        private_keys.sort_by(|(a, _), (b, _)| a.to_string().cmp(&b.to_string()));
+
+        let node_configs = private_keys
+            .into_iter()
+            .enumerate()
+            .map(
+                |(index, (account, (consensus_private_key, network_private_keys)))| {
+                    let peer_id = account.to_string();
+                    let node_output_dir = self.node_output_dir(index);
+                    fs::create_dir_all(&node_output_dir)
+                        .expect("Can not create validator output directory");
+                    let (listen_address, advertised_address) = self.node_addresses(index);
+
+                    self.generate_peer_info(
+                        &node_output_dir,
+                        &peer_id,
+                        &consensus_peers_config,
+                        &network_peers_config,
+                    );
+
+                    let consensus_config =
+                        self.generate_consensus_config(&node_output_dir, consensus_private_key);
+                    let network_config = self.generate_network_config(
+                        &node_output_dir,
+                        &peer_id,
+                        &listen_address,
+                        &advertised_address,
+                        network_private_keys,
+                    );
+
+                    self.generate_seed_peers_config(&node_output_dir, &network_config);
+
+                    self.assemble_node_config(&node_output_dir, network_config, consensus_config)
+                },
+            )
+            .collect();
+
+        Ok(node_configs)
+    }
 
-        let peer_id = network_peers_config.peers.keys().next().unwrap().to_owned();
+    /// Build nodes with no shared trusted peer set: full nodes (which
+    /// never participate in consensus) and/or permissionless validators.
+    /// Each node's network identity is generated independently, and none
+    /// of them get a `peer_info.toml` since they aren't part of any
+    /// trusted `ValidatorSet`.
+    fn build_standalone_nodes(self) -> Result<Vec<NodeConfig>, Error> {
+        let node_configs = (0..self.num_nodes)
+            .map(|index| {
+                let node_output_dir = self.node_output_dir(index);
+                fs::create_dir_all(&node_output_dir).expect("Can not create node output directory");
+                let (listen_address, advertised_address) = self.node_addresses(index);
+
+                let (identity_private_key, identity_public_key) =
+                    generate_ed25519_keypair(self.derived_key_seed(index, 0));
+                let (signing_private_key, _) =
+                    generate_ed25519_keypair(self.derived_key_seed(index, 1));
+                let peer_id = AccountAddress::from_public_key(&identity_public_key).to_string();
+
+                let network_private_keys = NetworkPrivateKeys {
+                    network_signing_private_key: signing_private_key,
+                    network_identity_private_key: identity_private_key,
+                };
+                let network_config = self.generate_network_config(
+                    &node_output_dir,
+                    &peer_id,
+                    &listen_address,
+                    &advertised_address,
+                    network_private_keys,
+                );
+
+                self.generate_seed_peers_config(&node_output_dir, &network_config);
+
+                let consensus_config = match self.role {
+                    RoleType::Validator => {
+                        let (consensus_private_key, _) =
+                            generate_ed25519_keypair(self.derived_key_seed(index, 2));
+                        self.generate_consensus_config(
+                            &node_output_dir,
+                            ConsensusPrivateKey {
+                                consensus_private_key,
+                            },
+                        )
+                    }
+                    RoleType::FullNode => ConsensusConfig::default(),
+                };
+
+                self.assemble_node_config(&node_output_dir, network_config, consensus_config)
+            })
+            .collect();
+
+        Ok(node_configs)
+    }
 
-        self.generate_peer_info(&peer_id, consensus_peers_config, network_peers_config);
+    /// Directory a given node's files are written to: `output_dir` itself
+    /// for a single node, or a numbered subdirectory for a swarm.
+    fn node_output_dir(&self, index: usize) -> PathBuf {
+        if self.num_nodes == 1 {
+            self.output_dir.clone()
+        } else {
+            self.output_dir.join(index.to_string())
+        }
+    }
 
-        let (_account, (consensus_private_key, network_private_keys)) =
-            private_keys.into_iter().next().unwrap();
+    /// Listen/advertised addresses for a given node: unchanged for a
+    /// single node, or offset by a distinct TCP port per node in a swarm.
+    fn node_addresses(&self, index: usize) -> (Multiaddr, Multiaddr) {
+        if self.num_nodes == 1 {
+            (self.listen_address.clone(), self.advertised_address.clone())
+        } else {
+            (
+                node_listen_address(&self.listen_address, index),
+                node_listen_address(&self.advertised_address, index),
+            )
+        }
+    }
 
-        let consensus_config = self.generate_consensus_config(consensus_private_key);
-        let network_config = self.generate_network_config(&peer_id, network_private_keys);
+    /// Derive a per-node, per-key seed from `key_seed` so standalone node
+    /// generation stays reproducible when a seed was explicitly provided.
+    /// Returns `None` (fresh OS randomness) when no seed was set.
+    fn derived_key_seed(&self, index: usize, tag: u8) -> Option<[u8; 32]> {
+        self.key_seed.map(|mut seed| {
+            seed[0] = seed[0].wrapping_add(index as u8);
+            seed[1] = seed[1].wrapping_add(tag);
+            seed
+        })
+    }
 
+    /// Assemble the final `NodeConfig` from its generated sub-configs and
+    /// write `node.config.toml`.
+    fn assemble_node_config(
+        &self,
+        output_dir: &Path,
+        network_config: NetworkConfig,
+        consensus_config: ConsensusConfig,
+    ) -> NodeConfig {
         let node_config = NodeConfig {
             base: Default::default(),
             networks: vec![network_config],
@@ -144,21 +321,23 @@ impl Builder {
             secret_service: Default::default(),
         };
 
-        let node_config_file = self.output_dir.join("node.config.toml");
+        let node_config_file = output_dir.join("node.config.toml");
         node_config.save_config(&node_config_file);
         status_ok!("Generated", "{}", node_config_file.display());
 
-        Ok(node_config)
+        node_config
     }
 
     /// Generate `ConsensusConfig` and write `consensus_keypair.config.toml`
-    fn generate_consensus_config(&self, private_key: ConsensusPrivateKey) -> ConsensusConfig {
+    fn generate_consensus_config(
+        &self,
+        output_dir: &Path,
+        private_key: ConsensusPrivateKey,
+    ) -> ConsensusConfig {
         let consensus_config = ConsensusConfig::default();
 
         let consensus_keypair = ConsensusKeyPair::load(Some(private_key.consensus_private_key));
-        let consensus_keypair_file = self
-            .output_dir
-            .join(&consensus_config.consensus_keypair_file);
+        let consensus_keypair_file = output_dir.join(&consensus_config.consensus_keypair_file);
 
         consensus_keypair.save_config(&consensus_keypair_file);
         status_ok!("Generated", "{}", consensus_keypair_file.display());
@@ -169,7 +348,10 @@ impl Builder {
     /// Generate `NetworkConfig` and write `network_keypairs.config.toml`
     fn generate_network_config(
         &self,
+        output_dir: &Path,
         peer_id: &str,
+        listen_address: &Multiaddr,
+        advertised_address: &Multiaddr,
         private_keys: NetworkPrivateKeys,
     ) -> NetworkConfig {
         let network_keypairs = NetworkKeyPairs::load(
@@ -186,32 +368,110 @@ impl Builder {
         }
         .to_owned();
 
-        network_config.listen_address = self.listen_address.clone();
-        network_config.advertised_address = self.advertised_address.clone();
+        network_config.listen_address = listen_address.clone();
+        network_config.advertised_address = advertised_address.clone();
         network_config.is_permissioned = self.is_permissioned;
 
-        let network_keypairs_file = self.output_dir.join(&network_config.network_keypairs_file);
+        let network_keypairs_file = output_dir.join(&network_config.network_keypairs_file);
         network_keypairs.save_config(&network_keypairs_file);
         status_ok!("Generated", "{}", network_keypairs_file.display());
 
         network_config
     }
 
-    /// Generate `PeerInfo` and write `peer_info.toml`
+    /// Write the recommended seed peer list to `seed_peers.config.toml`, so
+    /// this node dials those peers on startup. A no-op when no seed peers
+    /// were configured.
+    fn generate_seed_peers_config(&self, output_dir: &Path, network_config: &NetworkConfig) {
+        if self.seed_peers.is_empty() {
+            return;
+        }
+
+        let seed_peers_config = load_seed_peers_config(&self.seed_peers);
+        let seed_peers_file = output_dir.join(&network_config.seed_peers_file);
+        seed_peers_config.save_config(&seed_peers_file);
+        status_ok!("Generated", "{}", seed_peers_file.display());
+    }
+
+    /// Generate this validator's `PeerInfo` and write `peer_info.toml`.
+    /// Only this peer's own entry is written, so that running
+    /// `open-libra genesis` against every node's `peer_info.toml` merges
+    /// back into one consistent `ValidatorSet`.
     fn generate_peer_info(
         &self,
+        output_dir: &Path,
         peer_id: &str,
-        consensus_peers: ConsensusPeersConfig,
-        network_peers: NetworkPeersConfig,
+        consensus_peers: &ConsensusPeersConfig,
+        network_peers: &NetworkPeersConfig,
     ) -> PeerInfo {
-        let consensus_info = consensus_peers.peers.into_iter().next().unwrap().1;
-        let network_info = network_peers.peers.into_iter().next().unwrap().1;
+        let consensus_info = consensus_peers
+            .peers
+            .get(peer_id)
+            .expect("missing consensus peer for generated validator")
+            .clone();
+        let network_info = network_peers
+            .peers
+            .get(peer_id)
+            .expect("missing network peer for generated validator")
+            .clone();
         let peer_info = PeerInfo::new(peer_id, consensus_info, network_info);
 
-        let peer_info_file = self.output_dir.join(peer_info::DEFAULT_FILENAME);
+        let peer_info_file = output_dir.join(peer_info::DEFAULT_FILENAME);
         peer_info.save_config(&peer_info_file);
         status_ok!("Generated", "{}", peer_info_file.display());
 
         peer_info
     }
 }
+
+/// Derive the `index`-th node's address from `base` by appending a TCP
+/// port, starting at [`SWARM_BASE_PORT`] and incrementing per node. A
+/// trailing `Tcp` component `base` already carries is stripped first, so a
+/// configured address that already has a port (e.g. from `open-libra.toml`)
+/// doesn't end up with two.
+fn node_listen_address(base: &Multiaddr, index: usize) -> Multiaddr {
+    let mut address = without_tcp_port(base);
+    address.push(Protocol::Tcp(SWARM_BASE_PORT + index as u16));
+    address
+}
+
+/// Remove `addr`'s trailing `Tcp` component, if it has one.
+fn without_tcp_port(addr: &Multiaddr) -> Multiaddr {
+    let mut addr = addr.clone();
+    if let Some(Protocol::Tcp(_)) = addr.iter().last() {
+        addr.pop();
+    }
+    addr
+}
+
+/// Generate a fresh 32-byte seed from the OS RNG.
+fn random_seed() -> [u8; 32] {
+    let mut seed = [0u8; 32];
+    getrandom::getrandom(&mut seed).expect("RNG failure!");
+    seed
+}
+
+/// Generate an Ed25519 keypair, either deterministically from `seed` or
+/// freshly from the OS RNG when `seed` is `None`.
+fn generate_ed25519_keypair(seed: Option<[u8; 32]>) -> (Ed25519PrivateKey, Ed25519PublicKey) {
+    let bytes = seed.unwrap_or_else(random_seed);
+    let private_key = Ed25519PrivateKey::try_from(bytes.as_ref()).unwrap();
+    let public_key = Ed25519PublicKey::from(&private_key);
+    (private_key, public_key)
+}
+
+/// Load recommended seed peers from `peer_info.toml`-formatted files into
+/// a `SeedPeersConfig` keyed by peer ID.
+pub(crate) fn load_seed_peers_config(seed_peer_files: &[PathBuf]) -> SeedPeersConfig {
+    let mut seed_peers = HashMap::new();
+
+    for path in seed_peer_files {
+        let peer_info = PeerInfo::load_config(path);
+        seed_peers.insert(
+            peer_info.id.clone(),
+            vec![peer_info.network.advertised_address.clone()],
+        );
+    }
+
+    SeedPeersConfig { seed_peers }
+}
@@ -1,6 +1,8 @@
 //! `genesis` subcommand - generate `libra-node` configuration
 
-use crate::{peer_info::PeerInfo, prelude::*};
+mod keystore;
+
+use crate::{commands::config::builder::load_seed_peers_config, peer_info::PeerInfo, prelude::*};
 use abscissa_core::{Command, Options, Runnable};
 use libra_config::{
     config::PersistableConfig,
@@ -29,6 +31,28 @@ pub struct GenesisCmd {
     #[options(short = "o", long = "output", help = "output directory")]
     output_dir: Option<PathBuf>,
 
+    /// Load the faucet keypair from this file instead of generating one
+    #[options(
+        long = "faucet-key",
+        help = "load the faucet keypair from this path instead of generating one"
+    )]
+    faucet_key: Option<PathBuf>,
+
+    /// Encrypt the generated `mint.key` at rest using a passphrase
+    #[options(
+        long = "encrypt-faucet-key",
+        help = "encrypt the generated faucet keypair at rest with a passphrase"
+    )]
+    encrypt_faucet_key: bool,
+
+    /// `peer_info.toml`-formatted files of recommended seed peers to
+    /// announce to every new node, written out as `seed_peers.config.toml`
+    #[options(
+        long = "seed-peers",
+        help = "peer_info.toml files of recommended seed peers"
+    )]
+    seed_peers: Vec<PathBuf>,
+
     /// Paths to `peer_info.toml`-formatted files
     #[options(free, help = "peer_info.toml-formatted files")]
     peer_info_files: Vec<PathBuf>,
@@ -78,6 +102,13 @@ impl Runnable for GenesisCmd {
         let network_peers_file = output_dir.join("network_peers.config.toml");
         network_peers_config.save_config(&network_peers_file);
         status_ok!("Generated", "{}", network_peers_file.display());
+
+        if !self.seed_peers.is_empty() {
+            let seed_peers_config = load_seed_peers_config(&self.seed_peers);
+            let seed_peers_file = output_dir.join("seed_peers.config.toml");
+            seed_peers_config.save_config(&seed_peers_file);
+            status_ok!("Generated", "{}", seed_peers_file.display());
+        }
     }
 }
 
@@ -103,16 +134,28 @@ impl GenesisCmd {
         Ok(genesis_transaction)
     }
 
-    /// Load the faucet private key
-    // TODO(tarcieri): support for loading an existing faucet key from a file
+    /// Load the faucet private key, either from an existing keystore file
+    /// (`--faucet-key`) or by generating a fresh one.
     fn faucet_keypair(&self) -> Result<Ed25519KeyPair, io::Error> {
+        if let Some(faucet_key) = &self.faucet_key {
+            let keypair = keystore::load(faucet_key, keystore::read_passphrase)?;
+            status_ok!("Loaded", "{}", faucet_key.display());
+            return Ok(keypair);
+        }
+
         let mut key_bytes = [0u8; 32];
         getrandom::getrandom(&mut key_bytes).expect("RNG failure!");
         let key = Ed25519PrivateKey::try_from(key_bytes.as_ref()).unwrap();
         let keypair = Ed25519KeyPair::from(key);
 
+        let passphrase = if self.encrypt_faucet_key {
+            Some(keystore::read_passphrase()?)
+        } else {
+            None
+        };
+
         let mint_key_file = self.output_dir_or_default().join("mint.key");
-        File::create(&mint_key_file)?.write_all(&bincode::serialize(&keypair).unwrap())?;
+        keystore::save(&keypair, &mint_key_file, passphrase.as_deref())?;
         status_ok!("Generated", "{}", mint_key_file.display());
 
         Ok(keypair)
@@ -0,0 +1,281 @@
+//! Passphrase-protected keystore for the faucet (`mint.key`) keypair.
+//!
+//! Mirrors the way standalone Libra account managers keep validator keys
+//! on disk: a random salt feeds an scrypt KDF to derive a symmetric key,
+//! which is used to seal the Ed25519 private key seed with an AEAD under
+//! a random nonce. The sealed bundle is serialized as TOML alongside the
+//! KDF parameters so it stays decryptable even if the defaults change.
+//! Plaintext `bincode` files (the previous format) remain loadable.
+
+use super::Ed25519KeyPair;
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use libra_crypto::ed25519::Ed25519PrivateKey;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::io::{self, Write};
+use std::path::Path;
+use std::{env, fs};
+
+/// Environment variable consulted for the keystore passphrase before
+/// falling back to an interactive prompt.
+pub const PASSPHRASE_ENV_VAR: &str = "OPEN_LIBRA_FAUCET_PASSPHRASE";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+
+/// Upper bound on scrypt's `log_n` work parameter a loaded keystore may
+/// request. Scrypt's memory usage is `O(r * 2^log_n)`, so an unchecked
+/// value read from disk lets a corrupted or hostile `mint.key` force an
+/// allocation/CPU blowup before the passphrase is even checked. This is
+/// comfortably above [`KdfParams::default`]'s `log_n` of 15.
+const MAX_LOG_N: u8 = 20;
+
+/// Upper bound on scrypt's `r` (block size) work parameter.
+const MAX_R: u32 = 16;
+
+/// Upper bound on scrypt's `p` (parallelization) work parameter.
+const MAX_P: u32 = 4;
+
+/// scrypt work parameters, recorded alongside the ciphertext.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct KdfParams {
+    /// log2 of the scrypt cost parameter `N`
+    log_n: u8,
+    /// scrypt block size parameter `r`
+    r: u32,
+    /// scrypt parallelization parameter `p`
+    p: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        Self {
+            log_n: 15,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+/// On-disk representation of an encrypted faucet key.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+struct EncryptedKey {
+    kdf: KdfParams,
+    salt: Vec<u8>,
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+}
+
+/// Load a faucet keypair from `path`.
+///
+/// If the file holds an [`EncryptedKey`], `passphrase` is called to
+/// obtain the decryption passphrase. Otherwise the file is assumed to be
+/// a plaintext `bincode`-serialized keypair, for compatibility with
+/// files written before this module existed.
+pub fn load(
+    path: &Path,
+    passphrase: impl FnOnce() -> io::Result<String>,
+) -> io::Result<Ed25519KeyPair> {
+    let bytes = fs::read(path)?;
+
+    if let Ok(encrypted) = toml::from_slice::<EncryptedKey>(&bytes) {
+        let seed = decrypt(&encrypted, &passphrase()?)?;
+        let private_key = Ed25519PrivateKey::try_from(seed.as_ref())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        return Ok(Ed25519KeyPair::from(private_key));
+    }
+
+    bincode::deserialize(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Write `keypair` to `path`, encrypting it at rest when `passphrase` is
+/// `Some`; otherwise falls back to the plaintext `bincode` format.
+pub fn save(keypair: &Ed25519KeyPair, path: &Path, passphrase: Option<&str>) -> io::Result<()> {
+    let bytes = match passphrase {
+        Some(passphrase) => {
+            let seed = keypair.private_key.to_bytes();
+            let encrypted = encrypt(seed.as_ref(), passphrase)?;
+            toml::to_vec(&encrypted).expect("TOML serialization of EncryptedKey cannot fail")
+        }
+        None => bincode::serialize(keypair).unwrap(),
+    };
+
+    fs::File::create(path)?.write_all(&bytes)
+}
+
+/// Read the keystore passphrase from [`PASSPHRASE_ENV_VAR`], falling back
+/// to an interactive TTY prompt.
+pub fn read_passphrase() -> io::Result<String> {
+    if let Ok(passphrase) = env::var(PASSPHRASE_ENV_VAR) {
+        return Ok(passphrase);
+    }
+
+    rpassword::read_password_from_tty(Some("Faucet keystore passphrase: "))
+}
+
+/// Derive a 32-byte symmetric key from `passphrase` and `salt` using scrypt.
+fn derive_key(passphrase: &str, salt: &[u8], kdf: &KdfParams) -> io::Result<[u8; KEY_LEN]> {
+    let params = scrypt::Params::new(kdf.log_n, kdf.r, kdf.p)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    let mut key = [0u8; KEY_LEN];
+    scrypt::scrypt(passphrase.as_bytes(), salt, &params, &mut key)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+
+    Ok(key)
+}
+
+/// Seal `seed` (the 32-byte private key seed) under `passphrase`.
+fn encrypt(seed: &[u8], passphrase: &str) -> io::Result<EncryptedKey> {
+    let kdf = KdfParams::default();
+
+    let mut salt = [0u8; SALT_LEN];
+    getrandom::getrandom(&mut salt).expect("RNG failure!");
+
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    getrandom::getrandom(&mut nonce_bytes).expect("RNG failure!");
+
+    let key = derive_key(passphrase, &salt, &kdf)?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    let nonce = XNonce::from_slice(&nonce_bytes);
+
+    let ciphertext = cipher
+        .encrypt(nonce, seed)
+        .map_err(|_| io::Error::new(io::ErrorKind::Other, "faucet key encryption failed"))?;
+
+    Ok(EncryptedKey {
+        kdf,
+        salt: salt.to_vec(),
+        nonce: nonce_bytes.to_vec(),
+        ciphertext,
+    })
+}
+
+/// Reverse [`encrypt`], recovering the 32-byte private key seed.
+fn decrypt(encrypted: &EncryptedKey, passphrase: &str) -> io::Result<Vec<u8>> {
+    if encrypted.salt.len() != SALT_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("faucet keystore salt must be {} bytes", SALT_LEN),
+        ));
+    }
+
+    if encrypted.nonce.len() != NONCE_LEN {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("faucet keystore nonce must be {} bytes", NONCE_LEN),
+        ));
+    }
+
+    if encrypted.kdf.log_n > MAX_LOG_N || encrypted.kdf.r > MAX_R || encrypted.kdf.p > MAX_P {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "faucet keystore KDF parameters exceed sane maximums (log_n <= {}, r <= {}, p <= {})",
+                MAX_LOG_N, MAX_R, MAX_P
+            ),
+        ));
+    }
+
+    let key = derive_key(passphrase, &encrypted.salt, &encrypted.kdf)?;
+    let cipher = XChaCha20Poly1305::new(Key::from_slice(&key));
+    let nonce = XNonce::from_slice(&encrypted.nonce);
+
+    cipher
+        .decrypt(nonce, encrypted.ciphertext.as_ref())
+        .map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "failed to decrypt faucet key: wrong passphrase or corrupt file",
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn random_keypair() -> Ed25519KeyPair {
+        let mut bytes = [0u8; 32];
+        getrandom::getrandom(&mut bytes).unwrap();
+        let private_key = Ed25519PrivateKey::try_from(bytes.as_ref()).unwrap();
+        Ed25519KeyPair::from(private_key)
+    }
+
+    #[test]
+    fn encrypted_round_trip() {
+        let keypair = random_keypair();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mint.key");
+
+        save(&keypair, &path, Some("correct horse battery staple")).unwrap();
+        let loaded = load(&path, || Ok("correct horse battery staple".to_owned())).unwrap();
+
+        assert_eq!(
+            keypair.private_key.to_bytes(),
+            loaded.private_key.to_bytes()
+        );
+    }
+
+    #[test]
+    fn wrong_passphrase_is_a_graceful_error() {
+        let keypair = random_keypair();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mint.key");
+
+        save(&keypair, &path, Some("correct horse battery staple")).unwrap();
+
+        let result = load(&path, || Ok("incorrect passphrase".to_owned()));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn plaintext_keys_remain_loadable() {
+        let keypair = random_keypair();
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mint.key");
+
+        save(&keypair, &path, None).unwrap();
+
+        let loaded = load(&path, || {
+            panic!("plaintext keys should never need a passphrase")
+        })
+        .unwrap();
+        assert_eq!(
+            keypair.private_key.to_bytes(),
+            loaded.private_key.to_bytes()
+        );
+    }
+
+    #[test]
+    fn truncated_nonce_is_a_graceful_error() {
+        let keypair = random_keypair();
+        let seed = keypair.private_key.to_bytes();
+        let mut encrypted = encrypt(seed.as_ref(), "passphrase").unwrap();
+        encrypted.nonce.truncate(NONCE_LEN - 1);
+
+        assert!(decrypt(&encrypted, "passphrase").is_err());
+    }
+
+    #[test]
+    fn truncated_salt_is_a_graceful_error() {
+        let keypair = random_keypair();
+        let seed = keypair.private_key.to_bytes();
+        let mut encrypted = encrypt(seed.as_ref(), "passphrase").unwrap();
+        encrypted.salt.truncate(SALT_LEN - 1);
+
+        assert!(decrypt(&encrypted, "passphrase").is_err());
+    }
+
+    #[test]
+    fn oversized_kdf_params_are_a_graceful_error() {
+        let keypair = random_keypair();
+        let seed = keypair.private_key.to_bytes();
+        let mut encrypted = encrypt(seed.as_ref(), "passphrase").unwrap();
+        encrypted.kdf.log_n = MAX_LOG_N + 1;
+
+        assert!(decrypt(&encrypted, "passphrase").is_err());
+    }
+}
@@ -2,8 +2,44 @@
 
 use abscissa_core::Config;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// OpenLibra Configuration
+///
+/// Mirrors [`crate::commands::config::builder::Builder`]'s knobs so a
+/// persistent `open-libra.toml` can drive `config` without repeating every
+/// flag on the command line. Any CLI flag that's also set overrides the
+/// value loaded here.
 #[derive(Clone, Config, Debug, Default, Deserialize, Serialize)]
 #[serde(deny_unknown_fields)]
-pub struct AppConfig {}
+pub struct AppConfig {
+    /// Directory where config files will be output to, resolved against
+    /// this config file's parent directory if relative
+    #[serde(default)]
+    pub output_dir: Option<PathBuf>,
+
+    /// Address to listen on
+    #[serde(default)]
+    pub listen_address: Option<String>,
+
+    /// Address to advertise to the network
+    #[serde(default)]
+    pub advertised_address: Option<String>,
+
+    /// Node role: `validator` or `full_node`
+    #[serde(default)]
+    pub role: Option<String>,
+
+    /// Hex-encoded 32-byte seed used to deterministically generate keys
+    #[serde(default)]
+    pub key_seed: Option<String>,
+
+    /// Whether the generated network is permissioned
+    #[serde(default)]
+    pub is_permissioned: Option<bool>,
+
+    /// `peer_info.toml`-formatted files of recommended seed peers,
+    /// resolved against this config file's parent directory if relative
+    #[serde(default)]
+    pub seed_peers: Vec<PathBuf>,
+}
@@ -9,7 +9,10 @@
 
 use abscissa_core::testing::prelude::*;
 use lazy_static::lazy_static;
-use libra_config::config::{ConsensusConfig, NetworkConfig, NodeConfig, PersistableConfig};
+use libra_config::config::{
+    ConsensusConfig, NetworkConfig, NodeConfig, PersistableConfig, SeedPeersConfig,
+};
+use libra_config::trusted_peers::{ConsensusPeersConfig, NetworkPeersConfig};
 use open_libra::peer_info::PeerInfo;
 use tempfile::tempdir;
 
@@ -43,3 +46,170 @@ fn config_generator() {
     NodeConfig::load_config(dir.path().join("node.config.toml"));
     PeerInfo::load_config(dir.path().join("peer_info.toml"));
 }
+
+#[test]
+fn config_generator_full_node() {
+    let mut runner = RUNNER.clone();
+
+    let dir = tempdir().unwrap();
+    let cmd = runner
+        .arg("config")
+        .arg("-o")
+        .arg(dir.path())
+        .arg("--role")
+        .arg("full_node")
+        .capture_stdout()
+        .run();
+
+    cmd.wait().unwrap().expect_success();
+
+    // Full nodes don't participate in consensus, but still get a network
+    // identity and a node config; no `peer_info.toml` is generated since
+    // full nodes aren't part of any trusted `ValidatorSet`.
+    NetworkConfig::load_config(dir.path().join("network_keypairs.config.toml"));
+    let node_config = NodeConfig::load_config(dir.path().join("node.config.toml"));
+    assert_eq!(node_config.networks[0].role, "full_node");
+    assert!(!dir.path().join("peer_info.toml").exists());
+}
+
+#[test]
+fn swarm_genesis_validator_set() {
+    let mut runner = RUNNER.clone();
+
+    let config_dir = tempdir().unwrap();
+    let cmd = runner
+        .arg("config")
+        .arg("-o")
+        .arg(config_dir.path())
+        .arg("-n")
+        .arg("3")
+        .capture_stdout()
+        .run();
+
+    cmd.wait().unwrap().expect_success();
+
+    // Each validator's `peer_info.toml` lives in its own numbered subdirectory.
+    let peer_info_files: Vec<_> = (0..3)
+        .map(|i| config_dir.path().join(i.to_string()).join("peer_info.toml"))
+        .collect();
+
+    let genesis_dir = tempdir().unwrap();
+    let mut runner = RUNNER.clone();
+    let mut cmd = runner.arg("genesis").arg("-o").arg(genesis_dir.path());
+    for peer_info_file in &peer_info_files {
+        cmd = cmd.arg(peer_info_file);
+    }
+    cmd.capture_stdout().run().wait().unwrap().expect_success();
+
+    // Feeding all three `peer_info.toml` files back into `genesis` should
+    // recombine them into a single 3-member validator set.
+    let consensus_peers =
+        ConsensusPeersConfig::load_config(genesis_dir.path().join("consensus_peers.config.toml"));
+    assert_eq!(consensus_peers.peers.len(), 3);
+
+    let network_peers =
+        NetworkPeersConfig::load_config(genesis_dir.path().join("network_peers.config.toml"));
+    assert_eq!(network_peers.peers.len(), 3);
+}
+
+#[test]
+fn seed_peers_are_embedded_in_generated_configs() {
+    // Generate a single node to act as the recommended seed peer.
+    let seed_dir = tempdir().unwrap();
+    let mut runner = RUNNER.clone();
+    let cmd = runner
+        .arg("config")
+        .arg("-o")
+        .arg(seed_dir.path())
+        .capture_stdout()
+        .run();
+    cmd.wait().unwrap().expect_success();
+
+    let seed_peer_info = seed_dir.path().join("peer_info.toml");
+    let expected_peer_id = PeerInfo::load_config(&seed_peer_info).id;
+
+    // `config --seed-peers` should embed it in the generated node's
+    // `seed_peers.config.toml`.
+    let node_dir = tempdir().unwrap();
+    let mut runner = RUNNER.clone();
+    let cmd = runner
+        .arg("config")
+        .arg("-o")
+        .arg(node_dir.path())
+        .arg("--seed-peers")
+        .arg(&seed_peer_info)
+        .capture_stdout()
+        .run();
+    cmd.wait().unwrap().expect_success();
+
+    let seed_peers_config =
+        SeedPeersConfig::load_config(node_dir.path().join("seed_peers.config.toml"));
+    assert!(seed_peers_config.seed_peers.contains_key(&expected_peer_id));
+
+    // `genesis --seed-peers` should write the same list to the genesis
+    // output directory's `seed_peers.config.toml`.
+    let genesis_dir = tempdir().unwrap();
+    let mut runner = RUNNER.clone();
+    let cmd = runner
+        .arg("genesis")
+        .arg("-o")
+        .arg(genesis_dir.path())
+        .arg("--seed-peers")
+        .arg(&seed_peer_info)
+        .arg(&seed_peer_info)
+        .capture_stdout()
+        .run();
+    cmd.wait().unwrap().expect_success();
+
+    let seed_peers_config =
+        SeedPeersConfig::load_config(genesis_dir.path().join("seed_peers.config.toml"));
+    assert!(seed_peers_config.seed_peers.contains_key(&expected_peer_id));
+}
+
+#[test]
+fn app_config_merges_and_cli_overrides() {
+    let project_dir = tempdir().unwrap();
+
+    // A relative `output_dir` in `open-libra.toml` resolves against the
+    // config file's own directory, not the process's current directory.
+    std::fs::write(
+        project_dir.path().join("open-libra.toml"),
+        "output_dir = \"generated\"\nrole = \"full_node\"\n",
+    )
+    .unwrap();
+
+    let mut runner = RUNNER.clone();
+    let cmd = runner
+        .current_dir(project_dir.path())
+        .arg("config")
+        .capture_stdout()
+        .run();
+    cmd.wait().unwrap().expect_success();
+
+    let node_config = NodeConfig::load_config(
+        project_dir
+            .path()
+            .join("generated")
+            .join("node.config.toml"),
+    );
+    assert_eq!(node_config.networks[0].role, "full_node");
+
+    // A `--role` flag on the command line overrides the config file.
+    let mut runner = RUNNER.clone();
+    let cmd = runner
+        .current_dir(project_dir.path())
+        .arg("config")
+        .arg("--role")
+        .arg("validator")
+        .capture_stdout()
+        .run();
+    cmd.wait().unwrap().expect_success();
+
+    let node_config = NodeConfig::load_config(
+        project_dir
+            .path()
+            .join("generated")
+            .join("node.config.toml"),
+    );
+    assert_eq!(node_config.networks[0].role, "validator");
+}